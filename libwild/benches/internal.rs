@@ -31,5 +31,57 @@ fn layout_rules(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, layout_rules);
+/// A rule table with hundreds of `-ffunction-sections`/`-fdata-sections`
+/// style prefix rules, on top of the usual handful of defaults - the shape
+/// the byte-trie lookup is meant to stay fast against regardless of how many
+/// rules are registered.
+fn many_prefix_rules() -> LayoutRulesBuilder {
+    let mut builder = LayoutRulesBuilder::default();
+    for i in 0..500 {
+        builder.add_rule(format!(".text.section{i}.*").as_bytes(), ".text");
+        builder.add_rule(
+            format!(".data.rel.ro.section{i}.*").as_bytes(),
+            ".data.rel.ro",
+        );
+    }
+    builder
+}
+
+fn layout_rules_scaled(c: &mut Criterion) {
+    let lr = many_prefix_rules().build();
+
+    c.bench_function("layout_rules_lookup_hit_scaled", |b| {
+        b.iter(|| {
+            lr.section_rules.lookup(
+                b".text.section250.some_function",
+                SectionFlags::empty(),
+                sht::PROGBITS,
+            );
+        })
+    });
+
+    c.bench_function("layout_rules_lookup_miss_scaled", |b| {
+        b.iter(|| {
+            lr.section_rules
+                .lookup(b".nonexistent", SectionFlags::empty(), sht::PROGBITS);
+        })
+    });
+
+    c.bench_function("layout_rules_lookup_mixed_scaled", |b| {
+        b.iter(|| {
+            for r in [
+                ".text.section0.foo",
+                ".text.section499.bar",
+                ".data.rel.ro.section250.baz",
+                ".bss",
+                ".nonexistent",
+            ] {
+                lr.section_rules
+                    .lookup(r.as_bytes(), SectionFlags::empty(), sht::PROGBITS);
+            }
+        })
+    });
+}
+
+criterion_group!(benches, layout_rules, layout_rules_scaled);
 criterion_main!(benches);
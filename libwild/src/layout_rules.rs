@@ -0,0 +1,510 @@
+//! Rules that decide which output section each input section is placed into.
+//!
+//! A [`LayoutRulesBuilder`] accumulates placement rules - either the
+//! built-in defaults or ones supplied by a user (for example lowered from a
+//! linker script's `SECTIONS` block) - and [`build`](LayoutRulesBuilder::build)
+//! compiles them into a [`LayoutRules`] whose `section_rules` table is then
+//! consulted once per input section as the linker assigns it to an output
+//! section.
+
+mod glob;
+
+use glob::GlobPattern;
+use linker_utils::elf::SectionFlags;
+use std::collections::HashMap;
+
+/// The ELF `sh_type` of a section, used to disambiguate rules that would
+/// otherwise tie in [`SectionRules::lookup`].
+pub type Sht = u32;
+
+/// Accumulates placement rules before they're compiled into a [`LayoutRules`].
+///
+/// Rules are matched most-specific-first: an exact name beats a prefix
+/// pattern (`foo.*`), which beats a general glob (`foo.*.bar`), and among
+/// patterns of the same kind the one with the longest literal prefix wins.
+/// A rule with a `flags`/`sh_type` filter only matches sections that satisfy
+/// it; between two patterns of equal specificity, a filtered rule is tried
+/// before an unfiltered one.
+#[derive(Clone, Debug)]
+pub struct LayoutRulesBuilder {
+    rules: Vec<Rule>,
+    output_section_order: Vec<Box<str>>,
+    output_section_aligns: HashMap<Box<str>, u64>,
+}
+
+impl LayoutRulesBuilder {
+    pub fn new() -> Self {
+        Self {
+            rules: Vec::new(),
+            output_section_order: Vec::new(),
+            output_section_aligns: HashMap::new(),
+        }
+    }
+
+    /// Adds a rule mapping input sections matching `pattern` to
+    /// `output_section`, with no filters beyond the name pattern itself.
+    pub fn add_rule(&mut self, pattern: &[u8], output_section: &str) -> &mut Self {
+        self.push_rule(pattern, output_section, None, None, false)
+    }
+
+    /// Adds a rule that only matches sections whose flags/type also satisfy
+    /// `flags`/`sh_type`. Used to disambiguate patterns that would otherwise
+    /// tie on specificity, for example routing writable vs read-only data
+    /// sections that share a glob.
+    pub fn add_filtered_rule(
+        &mut self,
+        pattern: &[u8],
+        output_section: &str,
+        flags: Option<SectionFlags>,
+        sh_type: Option<Sht>,
+    ) -> &mut Self {
+        self.push_rule(pattern, output_section, flags, sh_type, false)
+    }
+
+    /// Adds a rule like [`add_rule`](Self::add_rule), but marks matching
+    /// input sections as kept, i.e. exempt from garbage collection, mirroring
+    /// a linker script's `KEEP(...)`.
+    pub fn add_kept_rule(&mut self, pattern: &[u8], output_section: &str) -> &mut Self {
+        self.push_rule(pattern, output_section, None, None, true)
+    }
+
+    /// Records the minimum alignment of `output_section`, as set by a linker
+    /// script's `ALIGN(...)` on the output section definition.
+    pub fn set_output_section_align(&mut self, output_section: &str, align: u64) -> &mut Self {
+        self.record_output_section(output_section);
+        self.output_section_aligns
+            .insert(output_section.into(), align);
+        self
+    }
+
+    fn push_rule(
+        &mut self,
+        pattern: &[u8],
+        output_section: &str,
+        flags: Option<SectionFlags>,
+        sh_type: Option<Sht>,
+        keep: bool,
+    ) -> &mut Self {
+        self.record_output_section(output_section);
+        self.rules.push(Rule {
+            pattern: Pattern::classify(pattern),
+            output_section: output_section.into(),
+            flags,
+            sh_type,
+            keep,
+        });
+        self
+    }
+
+    /// Notes `output_section` in the builder's output-section ordering, the
+    /// first time it's mentioned (whether via a rule or an `ALIGN`). Rules
+    /// added later for an already-known output section don't move it.
+    fn record_output_section(&mut self, output_section: &str) {
+        if !self
+            .output_section_order
+            .iter()
+            .any(|name| &**name == output_section)
+        {
+            self.output_section_order.push(output_section.into());
+        }
+    }
+
+    /// Compiles the accumulated rules into their lookup-ready form.
+    pub fn build(self) -> LayoutRules {
+        LayoutRules {
+            section_rules: SectionRules::compile(self.rules),
+            output_section_order: self.output_section_order,
+            output_section_aligns: self.output_section_aligns,
+        }
+    }
+}
+
+/// The default placement rules wild uses when the user hasn't supplied a
+/// linker script, mirroring the handful of output sections most object
+/// files' sections fall into.
+impl LayoutRulesBuilder {
+    /// Returns the builder seeded with wild's built-in default rules. This is
+    /// what [`LayoutRulesBuilder::default`] returns; kept as a separate
+    /// method so the set of built-in rules is easy to find.
+    fn with_builtin_defaults() -> Self {
+        let mut builder = Self::new();
+        builder
+            .add_rule(b".text", ".text")
+            .add_rule(b".text.*", ".text")
+            .add_rule(b".data", ".data")
+            .add_rule(b".data.*", ".data")
+            .add_rule(b".data.rel.ro", ".data.rel.ro")
+            .add_rule(b".data.rel.ro.*", ".data.rel.ro")
+            .add_rule(b".rodata", ".rodata")
+            .add_rule(b".rodata.*", ".rodata")
+            .add_rule(b".bss", ".bss")
+            .add_rule(b".bss.*", ".bss")
+            .add_rule(b".init_array", ".init_array")
+            .add_rule(b".init_array.*", ".init_array")
+            .add_rule(b".fini_array", ".fini_array")
+            .add_rule(b".fini_array.*", ".fini_array")
+            .add_rule(b".text.unlikely.*", ".text.unlikely")
+            .add_rule(b".text.hot.*", ".text.hot");
+        builder
+    }
+}
+
+/// A single name-pattern rule and the output section it routes matching
+/// input sections to.
+#[derive(Clone, Debug)]
+pub struct Rule {
+    pattern: Pattern,
+    output_section: Box<str>,
+    flags: Option<SectionFlags>,
+    sh_type: Option<Sht>,
+    keep: bool,
+}
+
+impl Rule {
+    /// The output section matching input sections are routed to.
+    pub fn output_section(&self) -> &str {
+        &self.output_section
+    }
+
+    /// Whether matching input sections should be exempt from garbage
+    /// collection, as set by a linker script's `KEEP(...)`.
+    pub fn keep(&self) -> bool {
+        self.keep
+    }
+
+    fn matches_filters(&self, flags: SectionFlags, sh_type: Sht) -> bool {
+        self.flags.is_none_or(|required| flags.contains(required))
+            && self.sh_type.is_none_or(|required| required == sh_type)
+    }
+
+    /// Whether this rule carries a `flags`/`sh_type` filter, making it
+    /// strictly more specific than an otherwise-identical rule without one.
+    fn has_filter(&self) -> bool {
+        self.flags.is_some() || self.sh_type.is_some()
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Pattern {
+    /// Matches only the exact section name.
+    Exact(Box<[u8]>),
+    /// Matches any name starting with this literal prefix. A pattern like
+    /// `.text.*` - a literal run followed by a single trailing `*` and
+    /// nothing else - is stored this way rather than as a general glob so it
+    /// can be compared by prefix length without running the glob matcher.
+    Prefix(Box<[u8]>),
+    /// A general glob containing `*`, `?` or a `[...]` class anywhere other
+    /// than as a single trailing `*`, along with the longest literal prefix
+    /// any matching name must start with.
+    Glob {
+        literal_prefix: Box<[u8]>,
+        glob: GlobPattern,
+    },
+}
+
+impl Pattern {
+    /// Classifies a raw pattern into the most specific representation that
+    /// fits it, so exact/prefix rules can be matched cheaply and ranked
+    /// above general globs.
+    fn classify(pattern: &[u8]) -> Pattern {
+        let is_wildcard = |&b: &u8| matches!(b, b'*' | b'?' | b'[');
+        match pattern.iter().position(is_wildcard) {
+            None => Pattern::Exact(pattern.into()),
+            Some(pos) if pos == pattern.len() - 1 && pattern[pos] == b'*' => {
+                Pattern::Prefix(pattern[..pos].into())
+            }
+            Some(_) => Pattern::Glob {
+                literal_prefix: glob::literal_prefix(pattern).into(),
+                glob: GlobPattern::compile(pattern),
+            },
+        }
+    }
+
+    fn literal_prefix_len(&self) -> usize {
+        match self {
+            Pattern::Exact(name) => name.len(),
+            Pattern::Prefix(prefix) => prefix.len(),
+            Pattern::Glob { literal_prefix, .. } => literal_prefix.len(),
+        }
+    }
+
+    fn matches(&self, name: &[u8]) -> bool {
+        match self {
+            Pattern::Exact(exact) => &**exact == name,
+            Pattern::Prefix(prefix) => name.starts_with(prefix),
+            Pattern::Glob { glob, .. } => glob.matches(name),
+        }
+    }
+}
+
+/// One node of the byte trie that backs exact/prefix lookups (see
+/// [`SectionRules`]). The node reached by walking a name's bytes from the
+/// root holds the rules whose pattern is exactly that path: `exact` for
+/// rules that must stop here (the name ends at this depth), `prefix` for
+/// rules that match here and at any greater depth reached through it.
+#[derive(Clone, Debug, Default)]
+struct TrieNode {
+    children: HashMap<u8, usize>,
+    exact: Vec<Rule>,
+    prefix: Vec<Rule>,
+}
+
+/// Follows (creating as needed) the path spelled out by `bytes` from the
+/// root, returning the index of the node at its end.
+fn intern_path(nodes: &mut Vec<TrieNode>, bytes: &[u8]) -> usize {
+    let mut node = 0;
+    for &byte in bytes {
+        node = match nodes[node].children.get(&byte) {
+            Some(&next) => next,
+            None => {
+                nodes.push(TrieNode::default());
+                let next = nodes.len() - 1;
+                nodes[node].children.insert(byte, next);
+                next
+            }
+        };
+    }
+    node
+}
+
+/// The first rule in `rules` whose filters are satisfied by `flags`/`sh_type`.
+/// `rules` is kept sorted filtered-rules-first by [`SectionRules::compile`],
+/// so this is the most specific match among same-length candidates.
+fn first_matching(rules: &[Rule], flags: SectionFlags, sh_type: Sht) -> Option<&Rule> {
+    rules.iter().find(|r| r.matches_filters(flags, sh_type))
+}
+
+/// The compiled, lookup-ready form of a rule set.
+///
+/// Exact and prefix rules are stored as a byte trie keyed on the pattern
+/// bytes: a name's depth in the trie *is* its match length, so
+/// [`lookup`](SectionRules::lookup) finds the longest matching prefix (or
+/// exact match) in a single pass over the name, independent of how many
+/// rules are registered. General globs can't be indexed this way and remain
+/// a list, scanned (longest literal prefix first) only when no exact/prefix
+/// rule matched - the uncommon case, since a real rule set is dominated by
+/// the `foo.*`-style prefixes `-ffunction-sections`/`-fdata-sections` output.
+#[derive(Clone, Debug)]
+pub struct SectionRules {
+    nodes: Vec<TrieNode>,
+    globs: Vec<Rule>,
+}
+
+impl Default for SectionRules {
+    fn default() -> Self {
+        SectionRules {
+            nodes: vec![TrieNode::default()],
+            globs: Vec::new(),
+        }
+    }
+}
+
+impl SectionRules {
+    fn compile(rules: Vec<Rule>) -> SectionRules {
+        let mut section_rules = SectionRules::default();
+        // Walk rules most-recently-added first, so a rule added later (e.g.
+        // lowered from a user's linker script) ends up ahead of an
+        // equally-specific one added earlier (e.g. a built-in default) once
+        // each node's lists are stably sorted below, instead of losing to it.
+        for rule in rules.into_iter().rev() {
+            match &rule.pattern {
+                Pattern::Exact(name) => {
+                    let node = intern_path(&mut section_rules.nodes, name);
+                    section_rules.nodes[node].exact.push(rule);
+                }
+                Pattern::Prefix(prefix) => {
+                    let node = intern_path(&mut section_rules.nodes, prefix);
+                    section_rules.nodes[node].prefix.push(rule);
+                }
+                Pattern::Glob { .. } => section_rules.globs.push(rule),
+            }
+        }
+        // A rule with a flags/type filter is more specific than one without,
+        // for the same name pattern, so put filtered rules first.
+        for node in &mut section_rules.nodes {
+            node.exact
+                .sort_by_key(|r| std::cmp::Reverse(r.has_filter()));
+            node.prefix
+                .sort_by_key(|r| std::cmp::Reverse(r.has_filter()));
+        }
+        section_rules
+            .globs
+            .sort_by_key(|r| std::cmp::Reverse(r.has_filter()));
+        // Longest literal prefix first, so the first glob that matches while
+        // scanning is already the most specific one.
+        section_rules
+            .globs
+            .sort_by_key(|r| std::cmp::Reverse(r.pattern.literal_prefix_len()));
+        section_rules
+    }
+
+    /// Finds the most specific rule matching `name`, restricted to rules
+    /// whose `flags`/`type` filters (if any) are satisfied by `flags` and
+    /// `sh_type`.
+    ///
+    /// Precedence: an exact name match beats a prefix match, which beats a
+    /// general glob match, and among prefixes/globs the one with the longest
+    /// literal prefix wins. Walks `name` once, byte by byte, through the
+    /// exact/prefix trie, tracking the deepest (i.e. longest) prefix match
+    /// seen; only falls back to scanning `globs` if that walk found neither
+    /// an exact nor a prefix match.
+    pub fn lookup(&self, name: &[u8], flags: SectionFlags, sh_type: Sht) -> Option<&Rule> {
+        let mut node = &self.nodes[0];
+        let mut best_prefix = first_matching(&node.prefix, flags, sh_type);
+        let mut name_fully_walked = true;
+        for &byte in name {
+            let Some(&next) = node.children.get(&byte) else {
+                name_fully_walked = false;
+                break;
+            };
+            node = &self.nodes[next];
+            if let Some(rule) = first_matching(&node.prefix, flags, sh_type) {
+                best_prefix = Some(rule);
+            }
+        }
+        if name_fully_walked {
+            if let Some(rule) = first_matching(&node.exact, flags, sh_type) {
+                return Some(rule);
+            }
+        }
+        best_prefix.or_else(|| {
+            self.globs
+                .iter()
+                .filter(|r| r.pattern.matches(name))
+                .find(|r| r.matches_filters(flags, sh_type))
+        })
+    }
+}
+
+/// The compiled set of layout rules the linker consults while assigning
+/// input sections to output sections.
+#[derive(Clone, Debug, Default)]
+pub struct LayoutRules {
+    pub section_rules: SectionRules,
+    output_section_order: Vec<Box<str>>,
+    output_section_aligns: HashMap<Box<str>, u64>,
+}
+
+impl LayoutRules {
+    /// The order in which output sections were first mentioned while
+    /// building these rules - for a script-driven build, this is the order
+    /// output sections appeared in the script.
+    pub fn output_section_order(&self) -> &[Box<str>] {
+        &self.output_section_order
+    }
+
+    /// The minimum alignment requested for `output_section`, if any,
+    /// e.g. via a linker script's `ALIGN(...)`.
+    pub fn output_section_align(&self, output_section: &str) -> Option<u64> {
+        self.output_section_aligns.get(output_section).copied()
+    }
+}
+
+impl Default for LayoutRulesBuilder {
+    fn default() -> Self {
+        // `#[derive(Default)]` on the struct would give us an empty builder;
+        // we want callers of `LayoutRulesBuilder::default()` to get wild's
+        // built-in rules instead. (`LayoutRules::default()`, by contrast, is
+        // an empty rule set.)
+        Self::with_builtin_defaults()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_beats_prefix_and_glob() {
+        let mut builder = LayoutRulesBuilder::new();
+        builder
+            .add_rule(b".text.*", ".text")
+            .add_rule(b".text.startup", ".text.startup")
+            .add_rule(b".text.*startup*", ".text.glob");
+        let lr = builder.build();
+
+        let rule = lr
+            .section_rules
+            .lookup(b".text.startup", SectionFlags::empty(), 0)
+            .unwrap();
+        assert_eq!(&*rule.output_section, ".text.startup");
+    }
+
+    #[test]
+    fn longer_prefix_beats_shorter_prefix() {
+        let mut builder = LayoutRulesBuilder::new();
+        builder
+            .add_rule(b".text.*", ".text")
+            .add_rule(b".text.unlikely.*", ".text.unlikely");
+        let lr = builder.build();
+
+        let rule = lr
+            .section_rules
+            .lookup(b".text.unlikely.foo", SectionFlags::empty(), 0)
+            .unwrap();
+        assert_eq!(&*rule.output_section, ".text.unlikely");
+
+        let rule = lr
+            .section_rules
+            .lookup(b".text.other", SectionFlags::empty(), 0)
+            .unwrap();
+        assert_eq!(&*rule.output_section, ".text");
+    }
+
+    #[test]
+    fn prefix_beats_general_glob_even_if_shorter() {
+        let mut builder = LayoutRulesBuilder::new();
+        builder
+            .add_rule(b".text.hot.*.cold", ".text.cold.glob")
+            .add_rule(b".text.*", ".text");
+        let lr = builder.build();
+
+        // Both rules match `.text.hot.foo.cold`. `.text.*` is a prefix rule
+        // and wins over the glob, even though its literal prefix (`.text.`)
+        // is shorter than the glob's (`.text.hot.`) - prefix-vs-glob is a
+        // structural distinction, not decided by prefix length.
+        let rule = lr
+            .section_rules
+            .lookup(b".text.hot.foo.cold", SectionFlags::empty(), 0)
+            .unwrap();
+        assert_eq!(&*rule.output_section, ".text");
+    }
+
+    #[test]
+    fn filters_disambiguate_ties() {
+        let mut builder = LayoutRulesBuilder::new();
+        builder
+            .add_filtered_rule(b".data.*", ".data.rel.ro", Some(SectionFlags::WRITE), None)
+            .add_rule(b".data.*", ".data");
+        let lr = builder.build();
+
+        let rule = lr
+            .section_rules
+            .lookup(b".data.foo", SectionFlags::WRITE, 0)
+            .unwrap();
+        assert_eq!(&*rule.output_section, ".data.rel.ro");
+
+        let rule = lr
+            .section_rules
+            .lookup(b".data.foo", SectionFlags::empty(), 0)
+            .unwrap();
+        assert_eq!(&*rule.output_section, ".data");
+    }
+
+    #[test]
+    fn default_rules_cover_common_sections() {
+        let lr = LayoutRulesBuilder::default().build();
+        assert!(lr
+            .section_rules
+            .lookup(b".text", SectionFlags::empty(), 0)
+            .is_some());
+        assert!(lr
+            .section_rules
+            .lookup(b".text.some-long-name", SectionFlags::empty(), 0)
+            .is_some());
+        assert!(lr
+            .section_rules
+            .lookup(b".nonexistent", SectionFlags::empty(), 0)
+            .is_none());
+    }
+}
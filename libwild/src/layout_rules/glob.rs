@@ -0,0 +1,172 @@
+//! Minimal glob matching for input-section name patterns.
+//!
+//! Supports the subset of shell-style globbing that GNU ld's `SECTIONS`
+//! wildcards use: `*` (any run of bytes, including none), `?` (exactly one
+//! byte) and `[...]` character classes (with `!` or `^` negation and byte
+//! ranges such as `[a-z]`). There is no escape character, matching ld's own
+//! behaviour.
+
+/// A compiled glob pattern over raw section-name bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GlobPattern {
+    tokens: Vec<Token>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Literal(u8),
+    AnyOne,
+    AnyRun,
+    Class {
+        negated: bool,
+        ranges: Vec<(u8, u8)>,
+    },
+}
+
+impl GlobPattern {
+    /// Compiles `pattern` into a matcher. `pattern` must contain at least one
+    /// of `*`, `?` or `[`, otherwise it should be treated as an exact or
+    /// prefix pattern instead (see [`super::Pattern::classify`]).
+    pub fn compile(pattern: &[u8]) -> GlobPattern {
+        let mut tokens = Vec::with_capacity(pattern.len());
+        let mut i = 0;
+        while i < pattern.len() {
+            match pattern[i] {
+                b'*' => {
+                    tokens.push(Token::AnyRun);
+                    i += 1;
+                }
+                b'?' => {
+                    tokens.push(Token::AnyOne);
+                    i += 1;
+                }
+                b'[' => {
+                    let (class, consumed) = parse_class(&pattern[i..]);
+                    tokens.push(class);
+                    i += consumed;
+                }
+                byte => {
+                    tokens.push(Token::Literal(byte));
+                    i += 1;
+                }
+            }
+        }
+        GlobPattern { tokens }
+    }
+
+    /// Returns whether `name` matches this pattern in full.
+    pub fn matches(&self, name: &[u8]) -> bool {
+        matches_from(&self.tokens, name)
+    }
+}
+
+/// Parses a `[...]` character class starting at `input[0] == b'['`, returning
+/// the token and the number of bytes of `input` it consumed. An unterminated
+/// class is treated as a literal `[`.
+fn parse_class(input: &[u8]) -> (Token, usize) {
+    debug_assert_eq!(input.first(), Some(&b'['));
+    let Some(close) = input.iter().position(|&b| b == b']').filter(|&p| p > 1) else {
+        return (Token::Literal(b'['), 1);
+    };
+    let mut body = &input[1..close];
+    let negated = matches!(body.first(), Some(b'!') | Some(b'^'));
+    if negated {
+        body = &body[1..];
+    }
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i < body.len() {
+        if i + 2 < body.len() && body[i + 1] == b'-' {
+            ranges.push((body[i], body[i + 2]));
+            i += 3;
+        } else {
+            ranges.push((body[i], body[i]));
+            i += 1;
+        }
+    }
+    (Token::Class { negated, ranges }, close + 1)
+}
+
+impl Token {
+    fn matches_byte(&self, byte: u8) -> bool {
+        match self {
+            Token::Literal(expected) => *expected == byte,
+            Token::AnyOne => true,
+            Token::AnyRun => true,
+            Token::Class { negated, ranges } => {
+                let hit = ranges.iter().any(|&(lo, hi)| lo <= byte && byte <= hi);
+                hit != *negated
+            }
+        }
+    }
+}
+
+/// Backtracking match of `tokens` against `name`, standard for small shell
+/// globs: `AnyRun` tries the shortest expansion first and grows on failure.
+fn matches_from(tokens: &[Token], name: &[u8]) -> bool {
+    match tokens.first() {
+        None => name.is_empty(),
+        Some(Token::AnyRun) => {
+            (0..=name.len()).any(|split| matches_from(&tokens[1..], &name[split..]))
+        }
+        Some(token) => match name.split_first() {
+            Some((&byte, rest)) => token.matches_byte(byte) && matches_from(&tokens[1..], rest),
+            None => false,
+        },
+    }
+}
+
+/// The longest literal (non-wildcard) prefix that any name matching `pattern`
+/// must start with. Used to order globs by specificity: a pattern whose
+/// matches are constrained by a longer literal prefix is preferred over one
+/// constrained by a shorter (or empty) prefix.
+pub fn literal_prefix(pattern: &[u8]) -> &[u8] {
+    let end = pattern
+        .iter()
+        .position(|&b| matches!(b, b'*' | b'?' | b'['))
+        .unwrap_or(pattern.len());
+    &pattern[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compiles(pattern: &str) -> GlobPattern {
+        GlobPattern::compile(pattern.as_bytes())
+    }
+
+    #[test]
+    fn star_matches_any_run() {
+        let g = compiles(".text.*");
+        assert!(g.matches(b".text.unlikely.foo"));
+        assert!(g.matches(b".text."));
+        assert!(!g.matches(b".text"));
+    }
+
+    #[test]
+    fn question_mark_matches_one_byte() {
+        let g = compiles(".text.?");
+        assert!(g.matches(b".text.a"));
+        assert!(!g.matches(b".text.ab"));
+        assert!(!g.matches(b".text."));
+    }
+
+    #[test]
+    fn character_class_and_negation() {
+        let g = compiles(".data.[a-c]*");
+        assert!(g.matches(b".data.abc"));
+        assert!(!g.matches(b".data.xyz"));
+
+        let g = compiles(".data.[!a-c]*");
+        assert!(!g.matches(b".data.abc"));
+        assert!(g.matches(b".data.xyz"));
+    }
+
+    #[test]
+    fn literal_prefix_stops_at_first_wildcard() {
+        assert_eq!(literal_prefix(b".text.unlikely.*"), b".text.unlikely.");
+        assert_eq!(literal_prefix(b"*"), b"");
+        assert_eq!(literal_prefix(b"plain"), b"plain");
+    }
+}
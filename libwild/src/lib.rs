@@ -0,0 +1,7 @@
+// Minimal crate root covering the pieces exercised by this snapshot.
+//
+// The real `libwild` crate is much larger; only the modules needed by the
+// code under active development here are declared.
+
+pub mod layout_rules;
+pub mod linker_script;
@@ -0,0 +1,382 @@
+//! A parser for the `SECTIONS` block of a GNU ld linker script.
+//!
+//! This covers the subset of linker-script syntax needed to drive wild's
+//! [`crate::layout_rules`] table from an existing embedded/kernel script:
+//! output-section definitions, input-section wildcard patterns
+//! (`*(.text .text.*)`), `KEEP(...)`, and an output section's `ALIGN(...)`.
+//! Anything else inside the `SECTIONS` block - symbol assignments,
+//! `PROVIDE`, `AT(...)`, `>region`, ... - isn't understood and is rejected,
+//! so a script that relies on such a feature fails to parse rather than
+//! silently linking something different from what it describes. Top-level
+//! commands before the `SECTIONS` block (`MEMORY`, `OUTPUT_FORMAT(...)`,
+//! ...) are skipped without being checked, and anything after the block is
+//! ignored.
+
+use crate::layout_rules::LayoutRulesBuilder;
+use std::fmt;
+
+/// Parses the first `SECTIONS { ... }` block found in `script` into a
+/// [`LayoutRulesBuilder`], seeded with wild's built-in default rules
+/// ([`LayoutRulesBuilder::default`]) so that input sections the script
+/// doesn't mention still resolve via those defaults.
+pub fn parse_sections(script: &str) -> Result<LayoutRulesBuilder, LinkerScriptError> {
+    let mut parser = Parser::new(script);
+    parser.parse_sections_block()
+}
+
+/// An error encountered while parsing a linker script's `SECTIONS` block.
+#[derive(Debug)]
+pub enum LinkerScriptError {
+    /// The script ended before a `SECTIONS { ... }` block was found.
+    MissingSectionsBlock,
+    /// The input ended in the middle of a construct.
+    UnexpectedEof { expected: &'static str },
+    /// A token was found where a different one was expected.
+    Unexpected {
+        expected: &'static str,
+        found: String,
+        offset: usize,
+    },
+    /// An `ALIGN(...)` argument wasn't a number we could parse.
+    InvalidNumber { text: String, offset: usize },
+}
+
+impl fmt::Display for LinkerScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkerScriptError::MissingSectionsBlock => {
+                write!(f, "no `SECTIONS {{ ... }}` block found in linker script")
+            }
+            LinkerScriptError::UnexpectedEof { expected } => {
+                write!(f, "unexpected end of linker script, expected {expected}")
+            }
+            LinkerScriptError::Unexpected {
+                expected,
+                found,
+                offset,
+            } => {
+                write!(f, "at byte {offset}: expected {expected}, found `{found}`")
+            }
+            LinkerScriptError::InvalidNumber { text, offset } => {
+                write!(f, "at byte {offset}: invalid number `{text}`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LinkerScriptError {}
+
+/// A hand-rolled recursive-descent parser over the raw script bytes.
+///
+/// Linker-script "words" (section names, wildcard patterns, keywords) share
+/// no fixed charset with the punctuation that structures the script, so
+/// rather than tokenizing up front we read structural characters (`{ } ( ) :`)
+/// one at a time and slurp everything else as a bareword.
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+/// Characters that stop a bareword (section name or wildcard pattern) and
+/// thus never appear inside one.
+fn is_structural(byte: u8) -> bool {
+    matches!(byte, b'{' | b'}' | b'(' | b')' | b':' | b',' | b';')
+}
+
+fn is_word_byte(byte: u8) -> bool {
+    !byte.is_ascii_whitespace() && !is_structural(byte)
+}
+
+impl<'a> Parser<'a> {
+    fn new(script: &'a str) -> Self {
+        Self {
+            bytes: script.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn parse_sections_block(&mut self) -> Result<LayoutRulesBuilder, LinkerScriptError> {
+        loop {
+            self.skip_trivia();
+            if self.pos >= self.bytes.len() {
+                return Err(LinkerScriptError::MissingSectionsBlock);
+            }
+            if self.eat_word("SECTIONS") {
+                break;
+            }
+            // Skip whatever top-level command precedes `SECTIONS` (memory
+            // regions, `OUTPUT_FORMAT(...)`, ...) one byte at a time; we
+            // only understand the `SECTIONS` block itself, and commands
+            // before it can contain punctuation a bareword reader would
+            // choke on.
+            self.pos += 1;
+        }
+
+        self.skip_trivia();
+        self.expect_byte(b'{', "`{` opening the SECTIONS block")?;
+
+        let mut builder = LayoutRulesBuilder::default();
+        loop {
+            self.skip_trivia();
+            if self.eat_byte(b'}') {
+                break;
+            }
+            self.parse_output_section(&mut builder)?;
+        }
+        Ok(builder)
+    }
+
+    fn parse_output_section(
+        &mut self,
+        builder: &mut LayoutRulesBuilder,
+    ) -> Result<(), LinkerScriptError> {
+        let name = self.read_word()?;
+        self.skip_trivia();
+        self.expect_byte(b':', "`:` after an output section name")?;
+
+        self.skip_trivia();
+        if self.eat_word("ALIGN") {
+            self.skip_trivia();
+            self.expect_byte(b'(', "`(` after ALIGN")?;
+            self.skip_trivia();
+            let align = self.read_number()?;
+            self.skip_trivia();
+            self.expect_byte(b')', "`)` closing ALIGN(...)")?;
+            builder.set_output_section_align(&name, align);
+            self.skip_trivia();
+        }
+
+        self.expect_byte(b'{', "`{` opening an output section's contents")?;
+        loop {
+            self.skip_trivia();
+            if self.eat_byte(b'}') {
+                break;
+            }
+            self.parse_input_section(builder, &name)?;
+        }
+        Ok(())
+    }
+
+    /// Parses one `*(pattern pattern ...)`, optionally wrapped in
+    /// `KEEP(...)`, adding a rule to `builder` for each pattern.
+    fn parse_input_section(
+        &mut self,
+        builder: &mut LayoutRulesBuilder,
+        output_section: &str,
+    ) -> Result<(), LinkerScriptError> {
+        self.skip_trivia();
+        let keep = self.eat_word("KEEP");
+        if keep {
+            self.skip_trivia();
+            self.expect_byte(b'(', "`(` after KEEP")?;
+            self.skip_trivia();
+        }
+
+        // We only support the common `*(...)` form (any input file); a
+        // specific file-name filter before `(` isn't handled.
+        self.expect_byte(b'*', "`*` selecting input files")?;
+        self.skip_trivia();
+        self.expect_byte(b'(', "`(` opening the input-section pattern list")?;
+        loop {
+            self.skip_trivia();
+            if self.eat_byte(b')') {
+                break;
+            }
+            let pattern = self.read_word()?;
+            if keep {
+                builder.add_kept_rule(pattern.as_bytes(), output_section);
+            } else {
+                builder.add_rule(pattern.as_bytes(), output_section);
+            }
+        }
+
+        if keep {
+            self.skip_trivia();
+            self.expect_byte(b')', "`)` closing KEEP(...)")?;
+        }
+        self.skip_trivia();
+        self.eat_byte(b';');
+        Ok(())
+    }
+
+    fn read_word(&mut self) -> Result<String, LinkerScriptError> {
+        self.skip_trivia();
+        let start = self.pos;
+        while self.pos < self.bytes.len() && is_word_byte(self.bytes[self.pos]) {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return Err(self.unexpected("a name or pattern"));
+        }
+        Ok(String::from_utf8_lossy(&self.bytes[start..self.pos]).into_owned())
+    }
+
+    fn read_number(&mut self) -> Result<u64, LinkerScriptError> {
+        let text = self.read_word()?;
+        let offset = self.pos - text.len();
+        let digits = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X"));
+        let parsed = match digits {
+            Some(hex) => u64::from_str_radix(hex, 16),
+            None => text.parse(),
+        };
+        parsed.map_err(|_| LinkerScriptError::InvalidNumber { text, offset })
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        self.skip_trivia();
+        let rest = &self.bytes[self.pos..];
+        let matches = rest.starts_with(word.as_bytes())
+            && rest.get(word.len()).is_none_or(|&b| !is_word_byte(b));
+        if matches {
+            self.pos += word.len();
+        }
+        matches
+    }
+
+    fn eat_byte(&mut self, byte: u8) -> bool {
+        self.skip_trivia();
+        if self.bytes.get(self.pos) == Some(&byte) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_byte(&mut self, byte: u8, expected: &'static str) -> Result<(), LinkerScriptError> {
+        if self.eat_byte(byte) {
+            Ok(())
+        } else if self.pos >= self.bytes.len() {
+            Err(LinkerScriptError::UnexpectedEof { expected })
+        } else {
+            Err(self.unexpected(expected))
+        }
+    }
+
+    fn unexpected(&self, expected: &'static str) -> LinkerScriptError {
+        let rest = &self.bytes[self.pos..];
+        let end = rest
+            .iter()
+            .position(|&b| b.is_ascii_whitespace() || is_structural(b))
+            .map_or(rest.len(), |end| end.max(1));
+        LinkerScriptError::Unexpected {
+            expected,
+            found: String::from_utf8_lossy(&rest[..end]).into_owned(),
+            offset: self.pos,
+        }
+    }
+
+    /// Skips whitespace and `/* ... */` comments.
+    fn skip_trivia(&mut self) {
+        loop {
+            while self
+                .bytes
+                .get(self.pos)
+                .is_some_and(|b| b.is_ascii_whitespace())
+            {
+                self.pos += 1;
+            }
+            if self.bytes[self.pos..].starts_with(b"/*") {
+                let close = self.bytes[self.pos + 2..]
+                    .windows(2)
+                    .position(|w| w == b"*/")
+                    .map_or(self.bytes.len(), |found| self.pos + 2 + found + 2);
+                self.pos = close;
+                continue;
+            }
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linker_utils::elf::SectionFlags;
+
+    #[test]
+    fn simple_sections_block() {
+        let script = r"
+            SECTIONS
+            {
+                .text : { *(.text .text.*) }
+                .rodata : ALIGN(16) { *(.rodata .rodata.*) }
+                .data : {
+                    *(.data)
+                    KEEP(*(.init_array))
+                }
+            }
+        ";
+        let lr = parse_sections(script).unwrap().build();
+
+        let rule = lr
+            .section_rules
+            .lookup(b".text.foo", SectionFlags::empty(), 0)
+            .unwrap();
+        assert_eq!(rule.output_section(), ".text");
+
+        let rule = lr
+            .section_rules
+            .lookup(b".init_array", SectionFlags::empty(), 0)
+            .unwrap();
+        assert_eq!(rule.output_section(), ".data");
+        assert!(rule.keep());
+
+        assert_eq!(lr.output_section_align(".rodata"), Some(16));
+        // `.text`, `.rodata` and `.data` are all already known from wild's
+        // built-in defaults (seeded before parsing), so the script's rules
+        // and `.rodata`'s `ALIGN` don't add a second entry for them.
+        assert_eq!(
+            lr.output_section_order()
+                .iter()
+                .filter(|s| &***s == ".rodata")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn script_rule_overrides_builtin_default_for_same_name() {
+        let script = r"
+            SECTIONS
+            {
+                .text : { *(.text.hot.*) }
+            }
+        ";
+        let lr = parse_sections(script).unwrap().build();
+
+        // `.text.hot.*` is also one of `LayoutRulesBuilder`'s built-in
+        // defaults, routing to `.text.hot`; the script's rule of equal
+        // specificity should win since it was added afterwards.
+        let rule = lr
+            .section_rules
+            .lookup(b".text.hot.foo", SectionFlags::empty(), 0)
+            .unwrap();
+        assert_eq!(rule.output_section(), ".text");
+    }
+
+    #[test]
+    fn unmatched_sections_fall_through_to_defaults() {
+        let script = r"
+            SECTIONS
+            {
+                .custom : { *(.custom) }
+            }
+        ";
+        let lr = parse_sections(script).unwrap().build();
+
+        // `.bss` isn't mentioned by the script, so the built-in default
+        // (seeded before parsing) still applies.
+        let rule = lr
+            .section_rules
+            .lookup(b".bss", SectionFlags::empty(), 0)
+            .unwrap();
+        assert_eq!(rule.output_section(), ".bss");
+    }
+
+    #[test]
+    fn missing_sections_block_is_an_error() {
+        let err = parse_sections("MEMORY { ram : ORIGIN = 0, LENGTH = 1K }").unwrap_err();
+        assert!(matches!(err, LinkerScriptError::MissingSectionsBlock));
+    }
+}